@@ -1,6 +1,5 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 use moka::sync::Cache;
-use std::time::Duration;
 
 pub fn insert_benchmark(c: &mut Criterion) {
     c.bench_function("cache_insert", |b| {
@@ -9,8 +9,8 @@ async fn main() {
     let cache = Cache::builder()
         .max_capacity(100)
         .time_to_live(Duration::from_secs(ttl))
-        .eviction_listener(|key, value, cause| {
-            println!("Evicted ({key:?},{value:?}) because {cause:?}");
+        .eviction_listener(|key, value, cause, name| {
+            println!("Evicted ({key:?},{value:?}) from {name:?} because {cause:?}");
         })
         .build();
 
@@ -27,7 +27,7 @@ async fn main() {
             // In a loop, read data from the socket and write the data back.
             loop {
                 let n = match socket.read(&mut buf).await {
-                    Ok(n) if n == 0 => return,
+                    Ok(0) => return,
                     Ok(n) => n,
                     Err(e) => {
                         println!("Failed to read from socket; err = {:?}", e);
@@ -0,0 +1,173 @@
+//! Types shared between the cache implementations in this crate.
+
+use std::time::{Duration, Instant};
+
+/// Indicates why an entry was removed from a cache.
+///
+/// Passed to an eviction listener registered via `CacheBuilder::eviction_listener`
+/// so callers can distinguish deliberate removals from ones driven by
+/// expiration or capacity pressure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RemovalCause {
+    /// The entry's expiration deadline passed (from `time_to_live`,
+    /// `time_to_idle`, an [`Expiry`] policy or a per-entry TTL).
+    Expired,
+    /// The entry was removed by an explicit `invalidate`.
+    Explicit,
+    /// The entry was overwritten by a new `insert` for the same key.
+    Replaced,
+    /// The entry was evicted to keep the cache within its `max_capacity`.
+    /// (An entry over `max_item_weight` is never stored, so it is not
+    /// reported to the listener.)
+    Size,
+}
+
+/// Running hit/miss/insertion/eviction counters for a cache.
+///
+/// Kept alongside a cache's other state and only updated when
+/// `CacheBuilder::record_stats(true)` is set; see [`CacheStats`] for the
+/// immutable snapshot handed out to callers.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct StatsCounters {
+    hit_count: u64,
+    miss_count: u64,
+    insertion_count: u64,
+    eviction_count: u64,
+}
+
+impl StatsCounters {
+    pub(crate) fn record_hit(&mut self) {
+        self.hit_count += 1;
+    }
+
+    pub(crate) fn record_miss(&mut self) {
+        self.miss_count += 1;
+    }
+
+    pub(crate) fn record_insertion(&mut self) {
+        self.insertion_count += 1;
+    }
+
+    pub(crate) fn record_eviction(&mut self) {
+        self.eviction_count += 1;
+    }
+
+    pub(crate) fn snapshot(&self, name: Option<&str>) -> CacheStats {
+        CacheStats {
+            name: name.map(str::to_owned),
+            hit_count: self.hit_count,
+            miss_count: self.miss_count,
+            insertion_count: self.insertion_count,
+            eviction_count: self.eviction_count,
+        }
+    }
+}
+
+/// A point-in-time snapshot of a cache's hit/miss/insertion/eviction counts.
+///
+/// Returned by `Cache::stats()`. The counters are only populated when the
+/// cache was built with `CacheBuilder::record_stats(true)`; otherwise every
+/// count is zero.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    name: Option<String>,
+    hit_count: u64,
+    miss_count: u64,
+    insertion_count: u64,
+    eviction_count: u64,
+}
+
+impl CacheStats {
+    /// Returns the name of the cache, if one was set with
+    /// `CacheBuilder::name`.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the number of times a `get` found an entry.
+    pub fn hit_count(&self) -> u64 {
+        self.hit_count
+    }
+
+    /// Returns the number of times a `get` found no entry (or an expired one).
+    pub fn miss_count(&self) -> u64 {
+        self.miss_count
+    }
+
+    /// Returns the total number of `get` calls, i.e. `hit_count + miss_count`.
+    pub fn request_count(&self) -> u64 {
+        self.hit_count + self.miss_count
+    }
+
+    /// Returns the ratio of hits to total requests, or `0.0` if there have
+    /// been no requests.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.request_count();
+        if total == 0 {
+            0.0
+        } else {
+            self.hit_count as f64 / total as f64
+        }
+    }
+
+    /// Returns the number of entries inserted.
+    pub fn insertion_count(&self) -> u64 {
+        self.insertion_count
+    }
+
+    /// Returns the number of entries evicted due to capacity or expiration
+    /// pressure, i.e. with [`RemovalCause::Expired`] or [`RemovalCause::Size`].
+    ///
+    /// Plain overwrites ([`RemovalCause::Replaced`]) and explicit
+    /// [`invalidate`][crate::sync::Cache::invalidate] calls
+    /// ([`RemovalCause::Explicit`]) are not counted here, since those aren't
+    /// what an operator means by "eviction" when tuning `max_capacity` or a
+    /// TTL/TTI.
+    pub fn eviction_count(&self) -> u64 {
+        self.eviction_count
+    }
+}
+
+/// A policy that computes a per-entry expiration duration.
+///
+/// Implement this trait and pass it to `CacheBuilder::expiry` to give entries
+/// a lifetime that depends on the key and/or value, instead of (or in
+/// addition to) a single cache-wide `time_to_live`/`time_to_idle`.
+///
+/// `expire_after_read` and `expire_after_update` receive `current_duration`,
+/// the time remaining until the entry's current expiration deadline (if
+/// any), and return:
+///
+/// - `None` to keep that previously computed deadline unchanged, or
+/// - `Some(duration)` to reset the deadline to `duration` from `now`.
+///
+/// `expire_after_create` returning `None` falls back to the cache-wide
+/// `time_to_live`/`time_to_idle`.
+pub trait Expiry<K, V> {
+    /// Called when an entry is inserted for a key that has no current value.
+    fn expire_after_create(&self, _key: &K, _value: &V, _now: Instant) -> Option<Duration> {
+        None
+    }
+
+    /// Called when an entry is read via `get`.
+    fn expire_after_read(
+        &self,
+        _key: &K,
+        _value: &V,
+        _now: Instant,
+        _current_duration: Option<Duration>,
+    ) -> Option<Duration> {
+        None
+    }
+
+    /// Called when an entry is overwritten by `insert`.
+    fn expire_after_update(
+        &self,
+        _key: &K,
+        _value: &V,
+        _now: Instant,
+        _current_duration: Option<Duration>,
+    ) -> Option<Duration> {
+        None
+    }
+}
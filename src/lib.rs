@@ -0,0 +1,13 @@
+//! # moka
+//!
+//! A fast, concurrent cache library inspired by the Caffeine library for Java.
+//!
+//! `moka` provides in-memory, key-value caches with optional size-based
+//! eviction, time-based expiration, and eviction notifications.
+
+mod common;
+
+pub mod sync;
+pub mod unsync;
+
+pub use common::{CacheStats, Expiry, RemovalCause};
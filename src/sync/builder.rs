@@ -0,0 +1,279 @@
+use super::{Cache, EvictionListener, Weigher};
+use crate::common::RemovalCause;
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
+
+const YEAR_SECONDS: u64 = 365 * 24 * 3600;
+
+/// Builds a [`Cache`][cache-struct] with various configuration knobs.
+///
+/// [cache-struct]: ./struct.Cache.html
+///
+/// # Examples
+///
+/// ```rust
+/// use moka::sync::Cache;
+/// use std::time::Duration;
+///
+/// let cache = Cache::builder()
+///     // Max 10,000 elements
+///     .max_capacity(10_000)
+///     // Time to live (TTL): 30 minutes
+///     .time_to_live(Duration::from_secs(30 * 60))
+///     // Time to idle (TTI):  5 minutes
+///     .time_to_idle(Duration::from_secs( 5 * 60))
+///     // Create the cache.
+///     .build();
+///
+/// cache.insert(0, "zero");
+/// ```
+///
+pub struct CacheBuilder<K, V, C> {
+    name: Option<String>,
+    max_capacity: Option<usize>,
+    max_item_weight: Option<u64>,
+    initial_capacity: Option<usize>,
+    weigher: Option<Weigher<K, V>>,
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+    ttl_resolution_ratio: Option<f64>,
+    eviction_listener: Option<EvictionListener<K, V>>,
+    record_stats: bool,
+    proactive_expiry_tick: Option<Duration>,
+    cache_type: PhantomData<C>,
+}
+
+impl<K, V> Default for CacheBuilder<K, V, Cache<K, V, RandomState>>
+where
+    K: Eq + Hash,
+{
+    fn default() -> Self {
+        Self {
+            name: None,
+            max_capacity: None,
+            max_item_weight: None,
+            initial_capacity: None,
+            weigher: None,
+            time_to_live: None,
+            time_to_idle: None,
+            ttl_resolution_ratio: None,
+            eviction_listener: None,
+            record_stats: false,
+            proactive_expiry_tick: None,
+            cache_type: Default::default(),
+        }
+    }
+}
+
+impl<K, V> CacheBuilder<K, V, Cache<K, V, RandomState>>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Construct a new `CacheBuilder` that will be used to build a `Cache` holding
+    /// up to `max_capacity` entries.
+    pub fn new(max_capacity: usize) -> Self {
+        Self {
+            max_capacity: Some(max_capacity),
+            ..Default::default()
+        }
+    }
+
+    /// Builds a `Cache<K, V>`.
+    pub fn build(self) -> Cache<K, V, RandomState> {
+        let build_hasher = RandomState::default();
+        self.time_to_live.map(|d| {
+            if Duration::from_secs(1_000 * YEAR_SECONDS) < d {
+                panic!("time_to_live is longer than 1000 years");
+            } else {
+                d
+            }
+        });
+        self.time_to_idle.map(|d| {
+            if Duration::from_secs(1_000 * YEAR_SECONDS) < d {
+                panic!("time_to_idle is longer than 1000 years");
+            } else {
+                d
+            }
+        });
+        if let Some(ratio) = self.ttl_resolution_ratio {
+            if !(ratio > 0.0 && ratio <= 1.0) {
+                panic!("ttl_resolution_ratio must be in the range (0.0, 1.0]");
+            }
+        }
+        Cache::with_everything(
+            self.name,
+            self.max_capacity,
+            self.max_item_weight,
+            self.initial_capacity,
+            build_hasher,
+            self.weigher,
+            self.time_to_live,
+            self.time_to_idle,
+            self.ttl_resolution_ratio,
+            self.eviction_listener,
+            self.record_stats,
+            self.proactive_expiry_tick,
+        )
+    }
+
+    /// Builds a `Cache<K, V, S>`, with the given `hasher`.
+    pub fn build_with_hasher<S>(self, hasher: S) -> Cache<K, V, S>
+    where
+        S: BuildHasher + Clone + Send + 'static,
+    {
+        Cache::with_everything(
+            self.name,
+            self.max_capacity,
+            self.max_item_weight,
+            self.initial_capacity,
+            hasher,
+            self.weigher,
+            self.time_to_live,
+            self.time_to_idle,
+            self.ttl_resolution_ratio,
+            self.eviction_listener,
+            self.record_stats,
+            self.proactive_expiry_tick,
+        )
+    }
+}
+
+impl<K, V, C> CacheBuilder<K, V, C> {
+    /// Sets the name of the cache, returned by [`Cache::name`].
+    ///
+    /// Useful for telling caches apart in logs and metrics when an
+    /// application runs many of them (e.g. "deferred rate limiter" vs
+    /// "response cache").
+    ///
+    /// [`Cache::name`]: ./struct.Cache.html#method.name
+    pub fn name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Sets the max capacity of the cache.
+    pub fn max_capacity(self, max_capacity: usize) -> Self {
+        Self {
+            max_capacity: Some(max_capacity),
+            ..self
+        }
+    }
+
+    /// Sets the initial capacity of the cache.
+    pub fn initial_capacity(self, capacity: usize) -> Self {
+        Self {
+            initial_capacity: Some(capacity),
+            ..self
+        }
+    }
+
+    /// Sets a per-entry weight ceiling, as computed by the configured
+    /// [`weigher`][Self::weigher] (or `1` if none is set).
+    ///
+    /// Entries inserted with [`Cache::insert_checked`] whose weight exceeds
+    /// this ceiling are rejected instead of being stored.
+    ///
+    /// [`Cache::insert_checked`]: ./struct.Cache.html#method.insert_checked
+    pub fn max_item_weight(self, weight: u64) -> Self {
+        Self {
+            max_item_weight: Some(weight),
+            ..self
+        }
+    }
+
+    /// Sets the weigher closure of the cache.
+    pub fn weigher(self, weigher: impl FnMut(&K, &V) -> u64 + Send + 'static) -> Self {
+        Self {
+            weigher: Some(Box::new(weigher)),
+            ..self
+        }
+    }
+
+    /// Sets the time to live of the cache.
+    ///
+    /// A cached entry will be expired after the specified duration past from
+    /// `insert`.
+    pub fn time_to_live(self, duration: Duration) -> Self {
+        Self {
+            time_to_live: Some(duration),
+            ..self
+        }
+    }
+
+    /// Sets the time to idle of the cache.
+    ///
+    /// A cached entry will be expired after the specified duration past from `get`
+    /// or `insert`.
+    pub fn time_to_idle(self, duration: Duration) -> Self {
+        Self {
+            time_to_idle: Some(duration),
+            ..self
+        }
+    }
+
+    /// Sets the ratio used to shorten an entry's effective TTL relative to
+    /// the "authoritative" remaining TTL passed to
+    /// [`Cache::insert_with_authoritative_ttl`][crate::sync::Cache::insert_with_authoritative_ttl].
+    ///
+    /// When set, such an entry's effective TTL becomes
+    /// `min(time_to_live, ttl * ratio)` instead of `ttl` outright, so
+    /// entries that are close to expiring at their source are refreshed
+    /// from it earlier. Must be in the range `(0.0, 1.0]`.
+    pub fn ttl_resolution_ratio(self, ratio: f64) -> Self {
+        Self {
+            ttl_resolution_ratio: Some(ratio),
+            ..self
+        }
+    }
+
+    /// Enables proactive background expiration: every `tick`, a background
+    /// thread scans for entries whose expiration instant has passed and
+    /// removes them (firing the eviction listener with
+    /// [`RemovalCause::Expired`]), so memory is reclaimed even for keys that
+    /// are never read again.
+    ///
+    /// The thread scans in bounded batches to avoid stalling concurrent
+    /// readers, and exits once the last `Cache` clone is dropped.
+    pub fn proactive_expiry(self, tick: Duration) -> Self {
+        Self {
+            proactive_expiry_tick: Some(tick),
+            ..self
+        }
+    }
+
+    /// Sets the eviction listener closure of the cache.
+    ///
+    /// The listener is called whenever an entry leaves the cache, with the
+    /// key, value, the [`RemovalCause`] that caused the removal, and the
+    /// cache's name (if one was set with [`Self::name`]).
+    pub fn eviction_listener(
+        self,
+        listener: impl FnMut(Arc<K>, V, RemovalCause, Option<&str>) + Send + 'static,
+    ) -> Self {
+        Self {
+            eviction_listener: Some(Box::new(listener)),
+            ..self
+        }
+    }
+
+    /// Enables or disables hit/miss/insertion/eviction statistics tracking.
+    ///
+    /// Statistics are not tracked by default, so that caches that don't read
+    /// them pay no bookkeeping cost. Once enabled, read a snapshot with
+    /// [`Cache::stats`][crate::sync::Cache::stats].
+    pub fn record_stats(self, enabled: bool) -> Self {
+        Self {
+            record_stats: enabled,
+            ..self
+        }
+    }
+}
@@ -0,0 +1,864 @@
+use crate::common::{CacheStats, RemovalCause, StatsCounters};
+
+use std::{
+    any::Any,
+    collections::{hash_map::RandomState, HashMap, VecDeque},
+    hash::{BuildHasher, Hash},
+    sync::{Arc, Condvar, Mutex, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The maximum number of raw entries a single proactive-expiry tick will
+/// visit, so that a tick never stalls concurrent readers for longer than it
+/// takes to scan a bounded batch, regardless of how many (if any) of those
+/// entries have actually expired. A cache larger than this is swept across
+/// multiple ticks via `Inner::proactive_expiry_cursor`.
+const PROACTIVE_EXPIRY_BATCH_SIZE: usize = 1024;
+
+/// A closure that computes the weight of a cache entry.
+///
+/// Unlike [`unsync::Weigher`][crate::unsync::Weigher], this closure must be
+/// `Send` so it can be moved into the cache's shared, `Arc`-wrapped state.
+pub type Weigher<K, V> = Box<dyn FnMut(&K, &V) -> u64 + Send>;
+
+/// A closure invoked when an entry leaves the cache.
+///
+/// Receives the key, value, the [`RemovalCause`] that caused the removal,
+/// and the cache's name (if one was set with
+/// [`CacheBuilder::name`][crate::sync::CacheBuilder::name]), so diagnostics
+/// can tell multiple caches apart.
+///
+/// Unlike [`unsync::EvictionListener`][crate::unsync::EvictionListener], this
+/// closure must be `Send` so it can be moved into the cache's shared,
+/// `Arc`-wrapped state.
+pub type EvictionListener<K, V> = Box<dyn FnMut(Arc<K>, V, RemovalCause, Option<&str>) + Send>;
+
+struct EntryData<V> {
+    value: V,
+    created_at: Instant,
+    expires_at: Option<Instant>,
+}
+
+struct Inner<K, V, S> {
+    entries: HashMap<K, EntryData<V>, S>,
+    order: VecDeque<K>,
+    name: Option<String>,
+    max_capacity: Option<usize>,
+    max_item_weight: Option<u64>,
+    weigher: Option<Weigher<K, V>>,
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+    ttl_resolution_ratio: Option<f64>,
+    eviction_listener: Option<EvictionListener<K, V>>,
+    record_stats: bool,
+    stats: StatsCounters,
+    proactive_expiry_cursor: usize,
+}
+
+impl<K, V, S> Inner<K, V, S> {
+    fn notify_eviction(&mut self, key: &K, value: V, cause: RemovalCause)
+    where
+        K: Clone,
+    {
+        if self.record_stats && matches!(cause, RemovalCause::Expired | RemovalCause::Size) {
+            self.stats.record_eviction();
+        }
+        let name = self.name.as_deref();
+        if let Some(listener) = &mut self.eviction_listener {
+            listener(Arc::new(key.clone()), value, cause, name);
+        }
+    }
+
+    /// Removes `key` from the insertion-order queue used to pick a victim for
+    /// capacity-based eviction. Must be called whenever an entry leaves
+    /// `entries` through any path other than that eviction itself (which
+    /// already pops its victim off the front), so `order` doesn't accumulate
+    /// one stale entry per expired/invalidated key forever.
+    fn remove_from_order(&mut self, key: &K)
+    where
+        K: PartialEq,
+    {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+    }
+}
+
+/// The shared state of an in-flight (or just-finished) `get_with`/
+/// `try_get_with` initializer for a single key.
+enum WaiterState<V> {
+    Pending,
+    Ready(V),
+    /// Holds the initializer's cloned error, type-erased so that `Waiter<V>`
+    /// doesn't need to carry the error type as a generic parameter (the
+    /// waiters map is shared by every `try_get_with::<E>` call, each of
+    /// which may use a different `E`).
+    Failed(Arc<dyn Any + Send + Sync>),
+    /// The leader's `init` panicked before it could record a result.
+    /// Followers that observe this retry by racing to become the new
+    /// leader, the same as if the key had never been requested.
+    Abandoned,
+}
+
+struct Waiter<V> {
+    state: Mutex<WaiterState<V>>,
+    condvar: Condvar,
+}
+
+/// Clears this key's waiter slot and marks it [`WaiterState::Abandoned`] if
+/// dropped without [`disarm`][Self::disarm] being called first, i.e. if the
+/// leader's `init` panics. Without this, a panicking initializer would leave
+/// the waiter permanently `Pending` and wedge every follower for that key.
+struct WaiterGuard<'a, K: Eq + Hash, V> {
+    waiters: &'a Mutex<HashMap<K, Arc<Waiter<V>>>>,
+    key: &'a K,
+    waiter: &'a Arc<Waiter<V>>,
+    disarmed: bool,
+}
+
+impl<'a, K: Eq + Hash, V> WaiterGuard<'a, K, V> {
+    fn disarm(mut self) {
+        self.disarmed = true;
+    }
+}
+
+impl<'a, K, V> Drop for WaiterGuard<'a, K, V>
+where
+    K: Eq + Hash,
+{
+    fn drop(&mut self) {
+        if !self.disarmed {
+            self.waiters.lock().unwrap().remove(self.key);
+            *self.waiter.state.lock().unwrap() = WaiterState::Abandoned;
+            self.waiter.condvar.notify_all();
+        }
+    }
+}
+
+/// A thread-safe, concurrent in-memory cache.
+///
+/// `sync::Cache` is cheap to clone; every clone refers to the same
+/// underlying store, so a single cache can be shared across threads by
+/// cloning it into each thread (or task) that needs it.
+pub struct Cache<K, V, S = RandomState> {
+    inner: Arc<Mutex<Inner<K, V, S>>>,
+    waiters: Arc<Mutex<HashMap<K, Arc<Waiter<V>>>>>,
+}
+
+impl<K, V, S> Clone for Cache<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            waiters: Arc::clone(&self.waiters),
+        }
+    }
+}
+
+impl<K, V> Cache<K, V, RandomState>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Returns a [`CacheBuilder`][crate::sync::CacheBuilder] for constructing
+    /// a `Cache` with no bound on its capacity by default.
+    pub fn builder() -> super::CacheBuilder<K, V, Self> {
+        super::CacheBuilder::default()
+    }
+}
+
+impl<K, V, S> Cache<K, V, S>
+where
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    S: BuildHasher,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn with_everything(
+        name: Option<String>,
+        max_capacity: Option<usize>,
+        max_item_weight: Option<u64>,
+        initial_capacity: Option<usize>,
+        build_hasher: S,
+        weigher: Option<Weigher<K, V>>,
+        time_to_live: Option<Duration>,
+        time_to_idle: Option<Duration>,
+        ttl_resolution_ratio: Option<f64>,
+        eviction_listener: Option<EvictionListener<K, V>>,
+        record_stats: bool,
+        proactive_expiry_tick: Option<Duration>,
+    ) -> Self
+    where
+        S: Send + 'static,
+    {
+        let inner = Arc::new(Mutex::new(Inner {
+            entries: HashMap::with_capacity_and_hasher(
+                initial_capacity.unwrap_or_default(),
+                build_hasher,
+            ),
+            order: VecDeque::new(),
+            name,
+            max_capacity,
+            max_item_weight,
+            weigher,
+            time_to_live,
+            time_to_idle,
+            ttl_resolution_ratio,
+            eviction_listener,
+            record_stats,
+            stats: StatsCounters::default(),
+            proactive_expiry_cursor: 0,
+        }));
+
+        if let Some(tick) = proactive_expiry_tick {
+            Self::spawn_proactive_expiry(Arc::downgrade(&inner), tick);
+        }
+
+        Self {
+            inner,
+            waiters: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Spawns a background thread that periodically scans for expired
+    /// entries and removes them, firing the eviction listener, so that
+    /// memory is reclaimed even for keys that are never read again.
+    ///
+    /// The thread holds `inner`'s lock only for bounded batches at a time,
+    /// and exits as soon as `inner` can no longer be upgraded, i.e. once the
+    /// last `Cache` clone has been dropped.
+    fn spawn_proactive_expiry(inner: Weak<Mutex<Inner<K, V, S>>>, tick: Duration)
+    where
+        S: Send + 'static,
+    {
+        thread::spawn(move || loop {
+            thread::sleep(tick);
+
+            let inner = match inner.upgrade() {
+                Some(inner) => inner,
+                None => return,
+            };
+            let mut inner = inner.lock().unwrap();
+
+            let now = Instant::now();
+            let len = inner.entries.len();
+            let scan_size = len.min(PROACTIVE_EXPIRY_BATCH_SIZE);
+            let cursor = inner.proactive_expiry_cursor % len.max(1);
+            inner.proactive_expiry_cursor = cursor + scan_size;
+
+            // Bound the raw iteration itself (not just the number of matches)
+            // by only ever visiting `scan_size` entries per tick, picking up
+            // from where the previous tick's cursor left off so a cache
+            // larger than one batch is swept across several ticks.
+            let expired: Vec<K> = inner
+                .entries
+                .iter()
+                .cycle()
+                .skip(cursor)
+                .take(scan_size)
+                .filter(|(_, entry)| matches!(entry.expires_at, Some(deadline) if deadline <= now))
+                .map(|(key, _)| key.clone())
+                .collect();
+
+            for key in expired {
+                if let Some(entry) = inner.entries.remove(&key) {
+                    inner.remove_from_order(&key);
+                    inner.notify_eviction(&key, entry.value, RemovalCause::Expired);
+                }
+            }
+        });
+    }
+
+    /// Returns the name of this cache, if one was set with
+    /// `CacheBuilder::name`.
+    ///
+    /// Useful for attributing log lines and metrics to a specific cache
+    /// (e.g. from inside an `eviction_listener` closure) when an application
+    /// runs many of them.
+    pub fn name(&self) -> Option<String> {
+        self.inner.lock().unwrap().name.clone()
+    }
+
+    /// Returns a snapshot of this cache's hit/miss/insertion/eviction counts.
+    ///
+    /// The counts are only tracked when the cache was built with
+    /// `CacheBuilder::record_stats(true)`.
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.inner.lock().unwrap();
+        inner.stats.snapshot(inner.name.as_deref())
+    }
+
+    /// Returns the max capacity of this cache.
+    pub fn max_capacity(&self) -> Option<usize> {
+        self.inner.lock().unwrap().max_capacity
+    }
+
+    /// Returns the time-to-live of this cache.
+    pub fn time_to_live(&self) -> Option<Duration> {
+        self.inner.lock().unwrap().time_to_live
+    }
+
+    /// Returns the time-to-idle of this cache.
+    pub fn time_to_idle(&self) -> Option<Duration> {
+        self.inner.lock().unwrap().time_to_idle
+    }
+
+    /// Inserts a key-value pair into the cache, using the cache-wide
+    /// `time_to_live`/`time_to_idle` to compute when this entry should
+    /// expire.
+    ///
+    /// If a `max_item_weight` is configured and this entry's weight exceeds
+    /// it, the entry is silently discarded instead of being stored; see
+    /// [`insert_checked`](Self::insert_checked) to observe that outcome.
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_with_deadline(key, value, None, true);
+    }
+
+    /// Inserts a key-value pair into the cache, treating `ttl` as the
+    /// remaining TTL known to an authoritative source (e.g. a database row
+    /// this cache fronts).
+    ///
+    /// If a [`ttl_resolution_ratio`][crate::sync::CacheBuilder::ttl_resolution_ratio]
+    /// is configured, the entry's effective TTL is shortened to
+    /// `min(time_to_live, ttl * ratio)`, so soon-to-expire entries are
+    /// refreshed from the source earlier. Otherwise `ttl` is used as-is,
+    /// overriding the cache's `time_to_live`/`time_to_idle` when setting this
+    /// entry's deadline. On a cache with `time_to_idle`, a later `get`
+    /// recomputes the deadline from `time_to_idle` (bounded by
+    /// `time_to_live` from insertion), replacing the one set here.
+    ///
+    /// Unlike [`unsync::Cache::insert_with_ttl`][crate::unsync::Cache::insert_with_ttl],
+    /// which always uses `ttl` as a hard literal override, this method may
+    /// shorten `ttl` per `ttl_resolution_ratio` above; hence the different
+    /// name.
+    pub fn insert_with_authoritative_ttl(&self, key: K, value: V, ttl: Duration) {
+        self.insert_with_deadline(key, value, Some(ttl), false);
+    }
+
+    /// Like [`insert`](Self::insert), but refuses to store the entry (and
+    /// returns `false`) if its weight, as computed by the configured
+    /// weigher, exceeds `max_item_weight`. A rejected entry never displaces
+    /// existing entries. Returns `true` if the entry was stored.
+    pub fn insert_checked(&self, key: K, value: V) -> bool {
+        self.insert_with_deadline(key, value, None, true)
+    }
+
+    fn insert_with_deadline(
+        &self,
+        key: K,
+        value: V,
+        explicit_ttl: Option<Duration>,
+        checked: bool,
+    ) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        if checked {
+            if let Some(ceiling) = inner.max_item_weight {
+                let weight = match &mut inner.weigher {
+                    Some(weigher) => weigher(&key, &value),
+                    None => 1,
+                };
+                if weight > ceiling {
+                    return false;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let existed = inner.entries.contains_key(&key);
+
+        let expires_at = match explicit_ttl {
+            Some(authoritative_ttl) => {
+                let effective_ttl = match inner.ttl_resolution_ratio {
+                    Some(ratio) => {
+                        let scaled = authoritative_ttl.mul_f64(ratio);
+                        match inner.time_to_live {
+                            Some(ttl) => ttl.min(scaled),
+                            None => scaled,
+                        }
+                    }
+                    None => authoritative_ttl,
+                };
+                Some(now + effective_ttl)
+            }
+            None => Self::default_deadline(&inner, now),
+        };
+
+        if !existed {
+            if let Some(max_capacity) = inner.max_capacity {
+                while inner.entries.len() >= max_capacity {
+                    if let Some(oldest) = inner.order.pop_front() {
+                        if let Some(evicted) = inner.entries.remove(&oldest) {
+                            inner.notify_eviction(&oldest, evicted.value, RemovalCause::Size);
+                        }
+                    } else {
+                        break;
+                    }
+                }
+            }
+            inner.order.push_back(key.clone());
+        }
+
+        let previous = inner.entries.insert(
+            key.clone(),
+            EntryData {
+                value,
+                created_at: now,
+                expires_at,
+            },
+        );
+
+        if let Some(previous) = previous {
+            inner.notify_eviction(&key, previous.value, RemovalCause::Replaced);
+        }
+
+        if inner.record_stats {
+            inner.stats.record_insertion();
+        }
+
+        true
+    }
+
+    fn default_deadline(inner: &Inner<K, V, S>, now: Instant) -> Option<Instant> {
+        match (inner.time_to_live, inner.time_to_idle) {
+            (Some(ttl), Some(tti)) => Some(now + ttl.min(tti)),
+            (Some(ttl), None) => Some(now + ttl),
+            (None, Some(tti)) => Some(now + tti),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns a clone of the value of the entry for `key`, if it exists and
+    /// has not expired.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.inner.lock().unwrap();
+        let now = Instant::now();
+
+        let expired = match inner.entries.get(key) {
+            Some(entry) => matches!(entry.expires_at, Some(deadline) if deadline <= now),
+            None => {
+                if inner.record_stats {
+                    inner.stats.record_miss();
+                }
+                return None;
+            }
+        };
+
+        if expired {
+            if let Some(entry) = inner.entries.remove(key) {
+                inner.remove_from_order(key);
+                inner.notify_eviction(key, entry.value, RemovalCause::Expired);
+            }
+            if inner.record_stats {
+                inner.stats.record_miss();
+            }
+            return None;
+        }
+
+        if inner.record_stats {
+            inner.stats.record_hit();
+        }
+
+        if let Some(tti) = inner.time_to_idle {
+            let ttl = inner.time_to_live;
+            if let Some(entry) = inner.entries.get_mut(key) {
+                let ttl_bound = ttl.map(|ttl| entry.created_at + ttl);
+                let mut new_deadline = now + tti;
+                if let Some(bound) = ttl_bound {
+                    new_deadline = new_deadline.min(bound);
+                }
+                entry.expires_at = Some(new_deadline);
+            }
+        }
+
+        inner.entries.get(key).map(|e| e.value.clone())
+    }
+
+    /// Removes the entry for `key` from the cache, returning its value if it
+    /// was present. An entry whose deadline has passed but that has not yet
+    /// been removed is returned (and reported as `Explicit`) too.
+    pub fn invalidate(&self, key: &K) -> Option<V> {
+        let mut inner = self.inner.lock().unwrap();
+        let removed = inner.entries.remove(key)?.value;
+        inner.remove_from_order(key);
+        inner.notify_eviction(key, removed.clone(), RemovalCause::Explicit);
+        Some(removed)
+    }
+
+    /// Returns the value for `key` if it exists, or inserts the value
+    /// returned by `init` and returns that.
+    ///
+    /// If many threads call `get_with` for the same, missing key at the same
+    /// time, only one of them runs `init`; the others block until that call
+    /// finishes and then receive its result.
+    pub fn get_with(&self, key: K, init: impl FnOnce() -> V) -> V {
+        #[derive(Clone)]
+        enum Never {}
+        let result: Result<V, Never> = self.get_or_insert_with(key, || Ok(init()));
+        match result {
+            Ok(v) => v,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Like [`get_with`](Self::get_with), but `init` may fail. If many
+    /// threads call `try_get_with` for the same, missing key at the same
+    /// time, only one of them runs `init`; if it returns `Err`, that error is
+    /// cloned and returned to every thread that was waiting on it, and the
+    /// key is left uncached so a later call may retry.
+    ///
+    /// All concurrent callers waiting on the same key must agree on `E`: the
+    /// in-flight waiter is shared by key alone, so racing `try_get_with::<E1>`
+    /// and `try_get_with::<E2>` calls for the same key is a logic error that
+    /// will panic in a follower thread when it can't downcast the leader's
+    /// error to its own `E`.
+    pub fn try_get_with<E>(&self, key: K, init: impl FnOnce() -> Result<V, E>) -> Result<V, E>
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        self.get_or_insert_with(key, init)
+    }
+
+    fn get_or_insert_with<F, E>(&self, key: K, init: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+        E: Clone + Send + Sync + 'static,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+
+        let (waiter, is_leader) = {
+            let mut waiters = self.waiters.lock().unwrap();
+            if let Some(waiter) = waiters.get(&key) {
+                (Arc::clone(waiter), false)
+            } else {
+                // A previous leader may have stored its value and cleared its
+                // waiter between the `get` above and taking this lock.
+                let inner = self.inner.lock().unwrap();
+                if let Some(entry) = inner.entries.get(&key) {
+                    if !matches!(entry.expires_at, Some(deadline) if deadline <= Instant::now()) {
+                        return Ok(entry.value.clone());
+                    }
+                }
+                drop(inner);
+                let waiter = Arc::new(Waiter {
+                    state: Mutex::new(WaiterState::Pending),
+                    condvar: Condvar::new(),
+                });
+                waiters.insert(key.clone(), Arc::clone(&waiter));
+                (waiter, true)
+            }
+        };
+
+        if is_leader {
+            let guard = WaiterGuard {
+                waiters: &self.waiters,
+                key: &key,
+                waiter: &waiter,
+                disarmed: false,
+            };
+
+            let result = init();
+            {
+                let mut state = waiter.state.lock().unwrap();
+                *state = match &result {
+                    Ok(value) => WaiterState::Ready(value.clone()),
+                    Err(error) => WaiterState::Failed(Arc::new(error.clone())),
+                };
+            }
+            if let Ok(value) = &result {
+                self.insert(key.clone(), value.clone());
+            }
+
+            self.waiters.lock().unwrap().remove(&key);
+            waiter.condvar.notify_all();
+            guard.disarm();
+
+            result
+        } else {
+            let mut state = waiter.state.lock().unwrap();
+            while matches!(*state, WaiterState::Pending) {
+                state = waiter.condvar.wait(state).unwrap();
+            }
+            match &*state {
+                WaiterState::Ready(value) => Ok(value.clone()),
+                WaiterState::Failed(error) => Err(error
+                    .downcast_ref::<E>()
+                    .expect("all waiters for a key share the same error type")
+                    .clone()),
+                WaiterState::Abandoned => {
+                    drop(state);
+                    self.get_or_insert_with(key, init)
+                }
+                WaiterState::Pending => unreachable!("just waited for this to change"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        sync::Arc,
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn eviction_listener_fires_with_correct_cause() {
+        use crate::common::RemovalCause;
+        use std::sync::Mutex as StdMutex;
+
+        let causes = Arc::new(StdMutex::new(Vec::new()));
+        let causes_clone = Arc::clone(&causes);
+
+        let cache: Cache<&'static str, u32> = Cache::builder()
+            .max_capacity(1)
+            .eviction_listener(move |_key, _value, cause, _name| {
+                causes_clone.lock().unwrap().push(cause);
+            })
+            .build();
+
+        cache.insert("a", 1);
+        cache.insert("a", 2); // Replaced
+        cache.insert("b", 3); // evicts "a" due to max_capacity == 1 (Size)
+        cache.invalidate(&"b"); // Explicit
+
+        let recorded = causes.lock().unwrap();
+        assert_eq!(
+            *recorded,
+            vec![
+                RemovalCause::Replaced,
+                RemovalCause::Size,
+                RemovalCause::Explicit
+            ]
+        );
+    }
+
+    #[test]
+    fn invalidate_prunes_order_so_reinserted_key_is_not_evicted_prematurely() {
+        let cache: Cache<&'static str, u32> = Cache::builder().max_capacity(2).build();
+
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.invalidate(&"a");
+        cache.insert("a", 3);
+        // If "a"'s stale entry were left in `order` from before the
+        // invalidate, this insert would evict the just-reinserted "a"
+        // instead of the actually-oldest live entry, "b".
+        cache.insert("c", 4);
+
+        assert_eq!(cache.get(&"a"), Some(3));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(4));
+    }
+
+    #[test]
+    fn stats_track_hits_and_misses() {
+        let cache: Cache<&'static str, u32> = Cache::builder().record_stats(true).build();
+
+        cache.insert("a", 1);
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.get(&"missing"), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hit_count(), 1);
+        assert_eq!(stats.miss_count(), 1);
+        assert_eq!(stats.insertion_count(), 1);
+        assert_eq!(stats.hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn eviction_count_only_counts_expired_and_size_causes() {
+        let cache: Cache<&'static str, u32> = Cache::builder()
+            .max_capacity(1)
+            .record_stats(true)
+            .build();
+
+        cache.insert("a", 1);
+        cache.insert("a", 2); // Replaced: not an eviction
+        cache.insert("b", 3); // Size: evicts "a"
+        cache.invalidate(&"b"); // Explicit: not an eviction
+
+        assert_eq!(cache.stats().eviction_count(), 1);
+    }
+
+    #[test]
+    fn basic_insert_and_get() {
+        let cache = Cache::builder().max_capacity(10).build();
+        cache.insert("a", 1);
+        assert_eq!(cache.get(&"a"), Some(1));
+        assert_eq!(cache.invalidate(&"a"), Some(1));
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn get_with_coalesces_concurrent_misses() {
+        let cache: Cache<&'static str, u32> = Cache::builder().max_capacity(10).build();
+        let call_count = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let call_count = Arc::clone(&call_count);
+                thread::spawn(move || {
+                    cache.get_with("key", || {
+                        call_count.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        42
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 42);
+        }
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn try_get_with_propagates_error_to_waiters() {
+        let cache: Cache<&'static str, u32> = Cache::builder().max_capacity(10).build();
+        let call_count = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = cache.clone();
+                let call_count = Arc::clone(&call_count);
+                thread::spawn(move || {
+                    cache.try_get_with("key", || {
+                        call_count.fetch_add(1, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        Err::<u32, _>("boom")
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Err("boom"));
+        }
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get(&"key"), None);
+    }
+
+    #[test]
+    fn get_with_recovers_after_leader_panics() {
+        let cache: Cache<&'static str, u32> = Cache::builder().max_capacity(10).build();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cache.get_with("key", || panic!("boom"))
+        }));
+        assert!(result.is_err());
+
+        // A panicking leader must not leave other callers (or later calls)
+        // permanently blocked on the abandoned waiter.
+        assert_eq!(cache.get_with("key", || 42), 42);
+    }
+
+    #[test]
+    fn max_item_weight_rejects_oversized_entries() {
+        let cache = Cache::builder()
+            .max_capacity(10)
+            .weigher(|_key, value: &&str| value.len() as u64)
+            .max_item_weight(5)
+            .build();
+
+        assert!(cache.insert_checked("a", "small"));
+        assert_eq!(cache.get(&"a"), Some("small"));
+
+        assert!(!cache.insert_checked("b", "too long"));
+        assert_eq!(cache.get(&"b"), None);
+    }
+
+    #[test]
+    fn max_item_weight_also_rejects_from_plain_insert() {
+        let cache = Cache::builder()
+            .weigher(|_key, value: &&str| value.len() as u64)
+            .max_item_weight(5)
+            .build();
+
+        cache.insert("a", "too long for the ceiling");
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    #[test]
+    fn build_cache_with_name() {
+        let cache: Cache<&'static str, u32> = Cache::builder().name("response cache").build();
+
+        assert_eq!(cache.name(), Some("response cache".to_string()));
+
+        cache.insert("a", 1);
+        cache.get(&"a");
+
+        assert_eq!(cache.stats().name(), Some("response cache"));
+    }
+
+    #[test]
+    fn proactive_expiry_reclaims_unread_entries() {
+        use crate::common::RemovalCause;
+        use std::sync::Mutex as StdMutex;
+
+        let causes = Arc::new(StdMutex::new(Vec::new()));
+        let causes_clone = Arc::clone(&causes);
+
+        let cache: Cache<&'static str, u32> = Cache::builder()
+            .time_to_live(Duration::from_millis(20))
+            .proactive_expiry(Duration::from_millis(10))
+            .eviction_listener(move |_key, _value, cause, _name| {
+                causes_clone.lock().unwrap().push(cause);
+            })
+            .build();
+
+        cache.insert("a", 1);
+        // Never read "a" again; only the background tick can reclaim it.
+        thread::sleep(Duration::from_millis(100));
+
+        assert_eq!(*causes.lock().unwrap(), vec![RemovalCause::Expired]);
+    }
+
+    #[test]
+    fn eviction_listener_receives_cache_name() {
+        use std::sync::Mutex as StdMutex;
+
+        let names = Arc::new(StdMutex::new(Vec::new()));
+        let names_clone = Arc::clone(&names);
+
+        let cache: Cache<&'static str, u32> = Cache::builder()
+            .max_capacity(1)
+            .name("response cache")
+            .eviction_listener(move |_key, _value, _cause, name| {
+                names_clone.lock().unwrap().push(name.map(str::to_owned));
+            })
+            .build();
+
+        cache.insert("a", 1);
+        cache.insert("b", 2); // evicts "a", firing the listener
+
+        assert_eq!(
+            *names.lock().unwrap(),
+            vec![Some("response cache".to_string())]
+        );
+    }
+
+    #[test]
+    fn ttl_resolution_ratio_shortens_authoritative_ttl() {
+        let cache: Cache<&'static str, u32> = Cache::builder()
+            .time_to_live(Duration::from_secs(60))
+            .ttl_resolution_ratio(0.5)
+            .build();
+
+        cache.insert_with_authoritative_ttl("a", 1, Duration::from_millis(20));
+        thread::sleep(Duration::from_millis(20));
+
+        // Authoritative TTL was 20ms, ratio 0.5 => effective TTL ~10ms.
+        assert_eq!(cache.get(&"a"), None);
+    }
+}
@@ -0,0 +1,7 @@
+//! A single-threaded, in-memory cache.
+
+mod builder;
+mod cache;
+
+pub use builder::CacheBuilder;
+pub use cache::{Cache, EvictionListener, Weigher};
@@ -1,9 +1,11 @@
-use super::{Cache, Weigher};
+use super::{Cache, EvictionListener, Weigher};
+use crate::common::{Expiry, RemovalCause};
 
 use std::{
     collections::hash_map::RandomState,
     hash::{BuildHasher, Hash},
     marker::PhantomData,
+    rc::Rc,
     time::Duration,
 };
 
@@ -16,12 +18,10 @@ const YEAR_SECONDS: u64 = 365 * 24 * 3600;
 /// # Examples
 ///
 /// ```rust
-/// use moka::unsync::Cache;
+/// use moka::unsync::{Cache, CacheBuilder};
 /// use std::time::Duration;
 ///
-/// let mut cache = Cache::builder()
-///     // Max 10,000 elements
-///     .max_capacity(10_000)
+/// let mut cache = CacheBuilder::new(10_000)
 ///     // Time to live (TTL): 30 minutes
 ///     .time_to_live(Duration::from_secs(30 * 60))
 ///     // Time to idle (TTI):  5 minutes
@@ -40,11 +40,16 @@ const YEAR_SECONDS: u64 = 365 * 24 * 3600;
 /// ```
 ///
 pub struct CacheBuilder<K, V, C> {
+    name: Option<String>,
     max_capacity: Option<usize>,
+    max_item_weight: Option<u64>,
     initial_capacity: Option<usize>,
     weigher: Option<Weigher<K, V>>,
     time_to_live: Option<Duration>,
     time_to_idle: Option<Duration>,
+    expiry: Option<Box<dyn Expiry<K, V>>>,
+    eviction_listener: Option<EvictionListener<K, V>>,
+    record_stats: bool,
     cache_type: PhantomData<C>,
 }
 
@@ -54,11 +59,16 @@ where
 {
     fn default() -> Self {
         Self {
+            name: None,
             max_capacity: None,
+            max_item_weight: None,
             initial_capacity: None,
             weigher: None,
             time_to_live: None,
             time_to_idle: None,
+            expiry: None,
+            eviction_listener: None,
+            record_stats: false,
             cache_type: Default::default(),
         }
     }
@@ -66,7 +76,7 @@ where
 
 impl<K, V> CacheBuilder<K, V, Cache<K, V, RandomState>>
 where
-    K: Eq + Hash,
+    K: Eq + Hash + Clone,
 {
     /// Construct a new `CacheBuilder` that will be used to build a `Cache` holding
     /// up to `max_capacity` entries.
@@ -95,12 +105,17 @@ where
             }
         });
         Cache::with_everything(
+            self.name,
             self.max_capacity,
+            self.max_item_weight,
             self.initial_capacity,
             build_hasher,
             self.weigher,
             self.time_to_live,
             self.time_to_idle,
+            self.expiry,
+            self.eviction_listener,
+            self.record_stats,
         )
     }
 
@@ -110,17 +125,36 @@ where
         S: BuildHasher + Clone,
     {
         Cache::with_everything(
+            self.name,
             self.max_capacity,
+            self.max_item_weight,
             self.initial_capacity,
             hasher,
             self.weigher,
             self.time_to_live,
             self.time_to_idle,
+            self.expiry,
+            self.eviction_listener,
+            self.record_stats,
         )
     }
 }
 
 impl<K, V, C> CacheBuilder<K, V, C> {
+    /// Sets the name of the cache, returned by [`Cache::name`].
+    ///
+    /// Useful for telling caches apart in logs and metrics when an
+    /// application runs many of them (e.g. "deferred rate limiter" vs
+    /// "response cache").
+    ///
+    /// [`Cache::name`]: ./struct.Cache.html#method.name
+    pub fn name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
     /// Sets the max capacity of the cache.
     pub fn max_capacity(self, max_capacity: usize) -> Self {
         Self {
@@ -137,6 +171,20 @@ impl<K, V, C> CacheBuilder<K, V, C> {
         }
     }
 
+    /// Sets a per-entry weight ceiling, as computed by the configured
+    /// [`weigher`][Self::weigher] (or `1` if none is set).
+    ///
+    /// Entries inserted with [`Cache::insert_checked`] whose weight exceeds
+    /// this ceiling are rejected instead of being stored.
+    ///
+    /// [`Cache::insert_checked`]: ./struct.Cache.html#method.insert_checked
+    pub fn max_item_weight(self, weight: u64) -> Self {
+        Self {
+            max_item_weight: Some(weight),
+            ..self
+        }
+    }
+
     /// Sets the weigher closure of the cache.
     pub fn weigher(self, weigher: impl FnMut(&K, &V) -> u64 + 'static) -> Self {
         Self {
@@ -166,6 +214,48 @@ impl<K, V, C> CacheBuilder<K, V, C> {
             ..self
         }
     }
+
+    /// Sets the [`Expiry`][crate::Expiry] policy of the cache.
+    ///
+    /// An `Expiry` lets each entry compute its own expiration duration from
+    /// its key and value, instead of relying solely on the cache-wide
+    /// `time_to_live`/`time_to_idle`. See [`Cache::insert_with_ttl`] for
+    /// setting a one-off duration at insert time instead.
+    ///
+    /// [`Cache::insert_with_ttl`]: ./struct.Cache.html#method.insert_with_ttl
+    pub fn expiry(self, expiry: impl Expiry<K, V> + 'static) -> Self {
+        Self {
+            expiry: Some(Box::new(expiry)),
+            ..self
+        }
+    }
+
+    /// Sets the eviction listener closure of the cache.
+    ///
+    /// The listener is called whenever an entry leaves the cache, with the
+    /// key, value, the [`RemovalCause`] that caused the removal, and the
+    /// cache's name (if one was set with [`Self::name`]).
+    pub fn eviction_listener(
+        self,
+        listener: impl FnMut(Rc<K>, V, RemovalCause, Option<&str>) + 'static,
+    ) -> Self {
+        Self {
+            eviction_listener: Some(Box::new(listener)),
+            ..self
+        }
+    }
+
+    /// Enables or disables hit/miss/insertion/eviction statistics tracking.
+    ///
+    /// Statistics are not tracked by default, so that caches that don't read
+    /// them pay no bookkeeping cost. Once enabled, read a snapshot with
+    /// [`Cache::stats`][crate::unsync::Cache::stats].
+    pub fn record_stats(self, enabled: bool) -> Self {
+        Self {
+            record_stats: enabled,
+            ..self
+        }
+    }
 }
 
 #[cfg(test)]
@@ -174,8 +264,8 @@ mod tests {
 
     use std::time::Duration;
 
-    #[tokio::test]
-    async fn build_cache() {
+    #[test]
+    fn build_cache() {
         // Cache<char, String>
         let mut cache = CacheBuilder::new(100).build();
 
@@ -199,9 +289,131 @@ mod tests {
         assert_eq!(cache.get(&'a'), Some(&"Alice"));
     }
 
-    #[tokio::test]
+    #[test]
+    fn build_cache_with_stats() {
+        let mut cache = CacheBuilder::new(100).record_stats(true).build();
+
+        cache.insert("a", 1);
+        assert_eq!(cache.get(&"a"), Some(&1));
+        assert_eq!(cache.get(&"missing"), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hit_count(), 1);
+        assert_eq!(stats.miss_count(), 1);
+        assert_eq!(stats.insertion_count(), 1);
+        assert_eq!(stats.request_count(), 2);
+        assert_eq!(stats.hit_rate(), 0.5);
+    }
+
+    #[test]
+    fn eviction_count_only_counts_expired_and_size_causes() {
+        let mut cache = CacheBuilder::new(1).record_stats(true).build();
+
+        cache.insert("a", 1);
+        cache.insert("a", 2); // Replaced: not an eviction
+        cache.insert("b", 3); // Size: evicts "a"
+        cache.invalidate(&"b"); // Explicit: not an eviction
+
+        assert_eq!(cache.stats().eviction_count(), 1);
+    }
+
+    #[test]
+    fn build_cache_with_eviction_listener() {
+        use crate::common::RemovalCause;
+        use std::{cell::RefCell, rc::Rc};
+
+        let causes = Rc::new(RefCell::new(Vec::new()));
+        let causes_clone = Rc::clone(&causes);
+
+        let mut cache = CacheBuilder::new(1)
+            .eviction_listener(move |_key, _value, cause, _name| {
+                causes_clone.borrow_mut().push(cause);
+            })
+            .build();
+
+        cache.insert("a", 1);
+        cache.insert("a", 2); // Replaced
+        cache.insert("b", 3); // evicts "a" (Size)
+        cache.invalidate(&"b"); // Explicit
+
+        assert_eq!(
+            *causes.borrow(),
+            vec![
+                RemovalCause::Replaced,
+                RemovalCause::Size,
+                RemovalCause::Explicit
+            ]
+        );
+    }
+
+    #[test]
+    fn eviction_listener_receives_cache_name() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let names = Rc::new(RefCell::new(Vec::new()));
+        let names_clone = Rc::clone(&names);
+
+        let mut cache = CacheBuilder::new(1)
+            .name("response cache")
+            .eviction_listener(move |_key, _value, _cause, name| {
+                names_clone.borrow_mut().push(name.map(str::to_owned));
+            })
+            .build();
+
+        cache.insert("a", 1);
+        cache.insert("b", 2); // evicts "a", firing the listener
+
+        assert_eq!(*names.borrow(), vec![Some("response cache".to_string())]);
+    }
+
+    #[test]
+    fn invalidate_prunes_order_so_reinserted_key_is_not_evicted_prematurely() {
+        let mut cache = CacheBuilder::new(2).build();
+
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.invalidate(&"a");
+        cache.insert("a", 3);
+        // If "a"'s stale entry were left in `order` from before the
+        // invalidate, this insert would evict the just-reinserted "a"
+        // instead of the actually-oldest live entry, "b".
+        cache.insert("c", 4);
+
+        assert_eq!(cache.get(&"a"), Some(&3));
+        assert_eq!(cache.get(&"b"), None);
+        assert_eq!(cache.get(&"c"), Some(&4));
+    }
+
+    #[test]
+    fn build_cache_with_expiry() {
+        use crate::common::Expiry;
+        use std::time::{Duration, Instant};
+
+        struct MyExpiry;
+
+        impl Expiry<&'static str, u32> for MyExpiry {
+            fn expire_after_create(
+                &self,
+                _key: &&'static str,
+                value: &u32,
+                _now: Instant,
+            ) -> Option<Duration> {
+                Some(Duration::from_secs(*value as u64))
+            }
+        }
+
+        let mut cache = CacheBuilder::new(100).expiry(MyExpiry).build();
+
+        cache.insert("a", 60);
+        assert_eq!(cache.get(&"a"), Some(&60));
+
+        cache.insert_with_ttl("b", 1, Duration::from_secs(0));
+        assert_eq!(cache.get(&"b"), None);
+    }
+
+    #[test]
     #[should_panic(expected = "time_to_live is longer than 1000 years")]
-    async fn build_cache_too_long_ttl() {
+    fn build_cache_too_long_ttl() {
         let thousand_years_secs: u64 = 1000 * 365 * 24 * 3600;
         let builder: CacheBuilder<char, String, _> = CacheBuilder::new(100);
         let duration = Duration::from_secs(thousand_years_secs);
@@ -210,9 +422,9 @@ mod tests {
             .build();
     }
 
-    #[tokio::test]
+    #[test]
     #[should_panic(expected = "time_to_idle is longer than 1000 years")]
-    async fn build_cache_too_long_tti() {
+    fn build_cache_too_long_tti() {
         let thousand_years_secs: u64 = 1000 * 365 * 24 * 3600;
         let builder: CacheBuilder<char, String, _> = CacheBuilder::new(100);
         let duration = Duration::from_secs(thousand_years_secs);
@@ -220,4 +432,41 @@ mod tests {
             .time_to_idle(duration + Duration::from_secs(1))
             .build();
     }
+
+    #[test]
+    fn build_cache_with_name() {
+        let mut cache = CacheBuilder::new(100).name("response cache").build();
+
+        assert_eq!(cache.name(), Some("response cache"));
+
+        cache.insert('a', "Alice");
+        cache.get(&'a');
+
+        assert_eq!(cache.stats().name(), Some("response cache"));
+    }
+
+    #[test]
+    fn build_cache_with_max_item_weight() {
+        let mut cache = CacheBuilder::new(100)
+            .weigher(|_key, value: &&str| value.len() as u64)
+            .max_item_weight(5)
+            .build();
+
+        assert!(cache.insert_checked("a", "small"));
+        assert_eq!(cache.get(&"a"), Some(&"small"));
+
+        assert!(!cache.insert_checked("b", "too long"));
+        assert_eq!(cache.get(&"b"), None);
+    }
+
+    #[test]
+    fn max_item_weight_also_rejects_from_plain_insert() {
+        let mut cache = CacheBuilder::new(100)
+            .weigher(|_key, value: &&str| value.len() as u64)
+            .max_item_weight(5)
+            .build();
+
+        cache.insert("a", "too long for the ceiling");
+        assert_eq!(cache.get(&"a"), None);
+    }
 }
@@ -0,0 +1,480 @@
+use crate::common::{CacheStats, Expiry, RemovalCause, StatsCounters};
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::{BuildHasher, Hash},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// A closure that computes the weight of a cache entry.
+pub type Weigher<K, V> = Box<dyn FnMut(&K, &V) -> u64>;
+
+/// A closure invoked when an entry leaves the cache.
+///
+/// Receives the key, value, the [`RemovalCause`] that caused the removal,
+/// and the cache's name (if one was set with
+/// [`CacheBuilder::name`][crate::unsync::CacheBuilder::name]), so
+/// diagnostics can tell multiple caches apart.
+pub type EvictionListener<K, V> = Box<dyn FnMut(Rc<K>, V, RemovalCause, Option<&str>)>;
+
+struct EntryData<V> {
+    value: V,
+    created_at: Instant,
+    expires_at: Option<Instant>,
+}
+
+/// An in-memory cache that is not thread-safe.
+///
+/// `unsync::Cache` builds on a [`std::collections::HashMap`] and must only be
+/// used from a single thread (or task).
+pub struct Cache<K, V, S> {
+    name: Option<String>,
+    max_capacity: Option<usize>,
+    max_item_weight: Option<u64>,
+    weigher: Option<Weigher<K, V>>,
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+    expiry: Option<Box<dyn Expiry<K, V>>>,
+    eviction_listener: Option<EvictionListener<K, V>>,
+    record_stats: bool,
+    stats: StatsCounters,
+    entries: HashMap<K, EntryData<V>, S>,
+    order: VecDeque<K>,
+}
+
+impl<K, V, S> Cache<K, V, S>
+where
+    K: Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn with_everything(
+        name: Option<String>,
+        max_capacity: Option<usize>,
+        max_item_weight: Option<u64>,
+        initial_capacity: Option<usize>,
+        build_hasher: S,
+        weigher: Option<Weigher<K, V>>,
+        time_to_live: Option<Duration>,
+        time_to_idle: Option<Duration>,
+        expiry: Option<Box<dyn Expiry<K, V>>>,
+        eviction_listener: Option<EvictionListener<K, V>>,
+        record_stats: bool,
+    ) -> Self {
+        Self {
+            name,
+            max_capacity,
+            max_item_weight,
+            weigher,
+            time_to_live,
+            time_to_idle,
+            expiry,
+            eviction_listener,
+            record_stats,
+            stats: StatsCounters::default(),
+            entries: HashMap::with_capacity_and_hasher(
+                initial_capacity.unwrap_or_default(),
+                build_hasher,
+            ),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn notify_eviction(&mut self, key: &K, value: V, cause: RemovalCause) {
+        if self.record_stats && matches!(cause, RemovalCause::Expired | RemovalCause::Size) {
+            self.stats.record_eviction();
+        }
+        let name = self.name.as_deref();
+        if let Some(listener) = &mut self.eviction_listener {
+            listener(Rc::new(key.clone()), value, cause, name);
+        }
+    }
+
+    /// Removes `key` from the insertion-order queue used to pick a victim for
+    /// capacity-based eviction. Must be called whenever an entry leaves
+    /// `entries` through any path other than that eviction itself (which
+    /// already pops its victim off the front), so `order` doesn't accumulate
+    /// one stale entry per expired/invalidated key forever.
+    fn remove_from_order(&mut self, key: &K) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+    }
+
+    /// Returns the name of this cache, if one was set with
+    /// `CacheBuilder::name`.
+    ///
+    /// Useful for attributing log lines and metrics to a specific cache
+    /// (e.g. from inside an `eviction_listener` closure) when an application
+    /// runs many of them.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns a snapshot of this cache's hit/miss/insertion/eviction counts.
+    ///
+    /// The counts are only tracked when the cache was built with
+    /// `CacheBuilder::record_stats(true)`.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.snapshot(self.name.as_deref())
+    }
+
+    /// Returns the max capacity of this cache.
+    pub fn max_capacity(&self) -> Option<usize> {
+        self.max_capacity
+    }
+
+    /// Returns the time-to-live of this cache.
+    pub fn time_to_live(&self) -> Option<Duration> {
+        self.time_to_live
+    }
+
+    /// Returns the time-to-idle of this cache.
+    pub fn time_to_idle(&self) -> Option<Duration> {
+        self.time_to_idle
+    }
+
+    /// Inserts a key-value pair into the cache, using the expiration policy
+    /// (if any) or the cache-wide `time_to_live`/`time_to_idle` to compute
+    /// when this entry should expire.
+    ///
+    /// If a `max_item_weight` is configured and this entry's weight exceeds
+    /// it, the entry is silently discarded instead of being stored; see
+    /// [`insert_checked`](Self::insert_checked) to observe that outcome.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.insert_with_deadline(key, value, None, true);
+    }
+
+    /// Inserts a key-value pair into the cache that expires after `ttl`,
+    /// overriding the cache's expiration policy and `time_to_live`/
+    /// `time_to_idle` when setting this entry's deadline. A later `get` still
+    /// recomputes the deadline from the policy's `expire_after_read` or, with
+    /// no policy, from `time_to_idle`, replacing the one set here.
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) {
+        self.insert_with_deadline(key, value, Some(ttl), false);
+    }
+
+    /// Like [`insert`](Self::insert), but refuses to store the entry (and
+    /// returns `false`) if its weight, as computed by the configured
+    /// weigher, exceeds `max_item_weight`. A rejected entry never displaces
+    /// existing entries. Returns `true` if the entry was stored.
+    pub fn insert_checked(&mut self, key: K, value: V) -> bool {
+        self.insert_with_deadline(key, value, None, true)
+    }
+
+    fn insert_with_deadline(
+        &mut self,
+        key: K,
+        value: V,
+        explicit_ttl: Option<Duration>,
+        checked: bool,
+    ) -> bool {
+        if checked {
+            if let Some(ceiling) = self.max_item_weight {
+                let weight = match &mut self.weigher {
+                    Some(weigher) => weigher(&key, &value),
+                    None => 1,
+                };
+                if weight > ceiling {
+                    return false;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let existed = self.entries.contains_key(&key);
+        let previous_expires_at = self.entries.get(&key).and_then(|e| e.expires_at);
+
+        // For an update, `Expiry::expire_after_update` returning `None` means
+        // "keep the previously computed deadline", so fall back to it
+        // directly rather than treating it like "no policy was configured"
+        // (which is what `default_deadline` would do).
+        let expires_at = if let Some(ttl) = explicit_ttl {
+            Some(now + ttl)
+        } else if let Some(expiry) = &self.expiry {
+            if existed {
+                let current_duration =
+                    previous_expires_at.map(|d| d.saturating_duration_since(now));
+                match expiry.expire_after_update(&key, &value, now, current_duration) {
+                    Some(duration) => Some(now + duration),
+                    None => previous_expires_at,
+                }
+            } else {
+                expiry
+                    .expire_after_create(&key, &value, now)
+                    .map(|d| now + d)
+                    .or_else(|| self.default_deadline(now))
+            }
+        } else {
+            self.default_deadline(now)
+        };
+
+        if !existed {
+            if let Some(max_capacity) = self.max_capacity {
+                while self.entries.len() >= max_capacity {
+                    if let Some(oldest) = self.order.pop_front() {
+                        if let Some(evicted) = self.entries.remove(&oldest) {
+                            self.notify_eviction(&oldest, evicted.value, RemovalCause::Size);
+                        }
+                    } else {
+                        break;
+                    }
+                }
+            }
+            self.order.push_back(key.clone());
+        }
+
+        let previous = self.entries.insert(
+            key.clone(),
+            EntryData {
+                value,
+                created_at: now,
+                expires_at,
+            },
+        );
+
+        if let Some(previous) = previous {
+            self.notify_eviction(&key, previous.value, RemovalCause::Replaced);
+        }
+
+        if self.record_stats {
+            self.stats.record_insertion();
+        }
+
+        true
+    }
+
+    fn default_deadline(&self, now: Instant) -> Option<Instant> {
+        match (self.time_to_live, self.time_to_idle) {
+            (Some(ttl), Some(tti)) => Some(now + ttl.min(tti)),
+            (Some(ttl), None) => Some(now + ttl),
+            (None, Some(tti)) => Some(now + tti),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns a reference to the value of the entry for `key`, updating the
+    /// entry's deadline via the expiration policy's `expire_after_read` if one
+    /// is configured, or else from `time_to_idle` (if set).
+    ///
+    /// Returns `None` if there is no entry for `key`, or if the entry has
+    /// expired.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let now = Instant::now();
+
+        if let Some(entry) = self.entries.get(key) {
+            if let Some(expires_at) = entry.expires_at {
+                if expires_at <= now {
+                    if let Some(expired) = self.entries.remove(key) {
+                        self.remove_from_order(key);
+                        self.notify_eviction(key, expired.value, RemovalCause::Expired);
+                    }
+                    if self.record_stats {
+                        self.stats.record_miss();
+                    }
+                    return None;
+                }
+            }
+        } else {
+            if self.record_stats {
+                self.stats.record_miss();
+            }
+            return None;
+        }
+
+        if self.record_stats {
+            self.stats.record_hit();
+        }
+
+        if let Some(expiry) = &self.expiry {
+            let entry = self.entries.get(key).expect("presence checked above");
+            let current_duration = entry.expires_at.map(|d| d.saturating_duration_since(now));
+            if let Some(duration) =
+                expiry.expire_after_read(key, &entry.value, now, current_duration)
+            {
+                let entry = self.entries.get_mut(key).expect("presence checked above");
+                entry.expires_at = Some(now + duration);
+            }
+        } else if let Some(tti) = self.time_to_idle {
+            let entry = self.entries.get_mut(key).expect("presence checked above");
+            let ttl_bound = self.time_to_live.map(|ttl| entry.created_at + ttl);
+            let mut new_deadline = now + tti;
+            if let Some(bound) = ttl_bound {
+                new_deadline = new_deadline.min(bound);
+            }
+            entry.expires_at = Some(new_deadline);
+        }
+
+        self.entries.get(key).map(|e| &e.value)
+    }
+
+    /// Removes the entry for `key` from the cache, returning its value if it
+    /// was present. An entry whose deadline has passed but that has not yet
+    /// been removed is returned (and reported as `Explicit`) too.
+    pub fn invalidate(&mut self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let removed = self.entries.remove(key)?.value;
+        self.remove_from_order(key);
+        self.notify_eviction(key, removed.clone(), RemovalCause::Explicit);
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cache;
+    use crate::common::Expiry;
+    use crate::unsync::CacheBuilder;
+
+    use std::time::{Duration, Instant};
+
+    struct ReadResets;
+
+    impl Expiry<&'static str, u32> for ReadResets {
+        fn expire_after_create(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+        ) -> Option<Duration> {
+            Some(Duration::from_secs(3600))
+        }
+
+        fn expire_after_read(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+            _current_duration: Option<Duration>,
+        ) -> Option<Duration> {
+            Some(Duration::ZERO)
+        }
+    }
+
+    #[test]
+    fn expire_after_read_resets_deadline() {
+        let mut cache: Cache<&'static str, u32, _> =
+            CacheBuilder::new(10).expiry(ReadResets).build();
+
+        cache.insert("a", 1);
+        // The deadline from expire_after_create is an hour out, so this read
+        // succeeds and resets the deadline to "now" (Duration::ZERO out).
+        assert_eq!(cache.get(&"a"), Some(&1));
+        // Time has moved forward since the read above set the deadline, so
+        // the entry is now expired.
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    struct ReadKeepsPrevious;
+
+    impl Expiry<&'static str, u32> for ReadKeepsPrevious {
+        fn expire_after_create(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+        ) -> Option<Duration> {
+            Some(Duration::from_secs(3600))
+        }
+
+        fn expire_after_read(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+            _current_duration: Option<Duration>,
+        ) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn expire_after_read_none_keeps_previous_deadline() {
+        let mut cache: Cache<&'static str, u32, _> =
+            CacheBuilder::new(10).expiry(ReadKeepsPrevious).build();
+
+        cache.insert("a", 1);
+        // expire_after_read returns None on every read, so the hour-out
+        // deadline from expire_after_create is left untouched instead of
+        // collapsing to "no expiry".
+        for _ in 0..3 {
+            assert_eq!(cache.get(&"a"), Some(&1));
+        }
+    }
+
+    struct UpdateResets;
+
+    impl Expiry<&'static str, u32> for UpdateResets {
+        fn expire_after_create(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+        ) -> Option<Duration> {
+            Some(Duration::from_secs(3600))
+        }
+
+        fn expire_after_update(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+            _current_duration: Option<Duration>,
+        ) -> Option<Duration> {
+            Some(Duration::ZERO)
+        }
+    }
+
+    #[test]
+    fn expire_after_update_resets_deadline() {
+        let mut cache: Cache<&'static str, u32, _> =
+            CacheBuilder::new(10).expiry(UpdateResets).build();
+
+        cache.insert("a", 1);
+        // Overwriting the entry runs expire_after_update, which resets the
+        // deadline to "now" (Duration::ZERO out).
+        cache.insert("a", 2);
+        // Time has moved forward since the insert above set the deadline, so
+        // the entry is now expired.
+        assert_eq!(cache.get(&"a"), None);
+    }
+
+    struct UpdateKeepsPrevious;
+
+    impl Expiry<&'static str, u32> for UpdateKeepsPrevious {
+        fn expire_after_create(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+        ) -> Option<Duration> {
+            Some(Duration::from_secs(3600))
+        }
+
+        fn expire_after_update(
+            &self,
+            _key: &&'static str,
+            _value: &u32,
+            _now: Instant,
+            _current_duration: Option<Duration>,
+        ) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn expire_after_update_none_keeps_previous_deadline() {
+        let mut cache: Cache<&'static str, u32, _> =
+            CacheBuilder::new(10).expiry(UpdateKeepsPrevious).build();
+
+        cache.insert("a", 1);
+        // expire_after_update returns None, so the hour-out deadline from
+        // expire_after_create is left untouched instead of collapsing to
+        // "no expiry".
+        cache.insert("a", 2);
+        assert_eq!(cache.get(&"a"), Some(&2));
+    }
+}